@@ -6,33 +6,61 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 
-use p3_field::PrimeCharacteristicRing;
+use num_bigint::BigUint;
+use p3_field::{Field, PrimeCharacteristicRing};
 use p3_poseidon2::{
     ExternalLayer, ExternalLayerConstants, ExternalLayerConstructor, HLMDSMat4, InternalLayer,
     InternalLayerConstructor, Poseidon2, add_rc_and_sbox_generic, external_initial_permute_state,
-    external_terminal_permute_state, internal_permute_state,
+    external_terminal_permute_state,
 };
 
-use crate::Bn254;
+use crate::helpers::monty_mul;
+use crate::{BN254_MONTY_R_SQ, Bn254};
 
-/// Degree of the chosen permutation polynomial for BN254, used as the Poseidon2 S-Box.
+/// The BN254 scalar field modulus, used to bound the output of the Grain LFSR
+/// round-constant generator.
+const BN254_MODULUS: &str =
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+/// `ceil(log2(p))` for the BN254 scalar field modulus.
+const BN254_NUM_BITS: usize = 254;
+
+/// Default degree of the chosen permutation polynomial for BN254, used as the Poseidon2 S-Box.
 ///
 /// As p - 1 is divisible by 2 and 3 the smallest choice for a degree D satisfying gcd(p - 1, D) = 1 is 5.
 const BN254_S_BOX_DEGREE: u64 = 5;
 
+/// Sentinel S-box "degree" requesting the inverse S-box `x -> x^{-1}` (with
+/// `0` mapped to itself) rather than a power map. `0` is never a valid power
+/// S-box exponent, so it is free to repurpose as an id for this alternate
+/// instance; see [`bn254_add_rc_and_sbox`].
+const BN254_S_BOX_DEGREE_INVERSE: u64 = 0;
+
 /// An implementation of the Poseidon2 hash function for the Bn254Fr field.
 ///
-/// It acts on arrays of the form `[Bn254Fr; WIDTH]`.
-pub type Poseidon2Bn254<const WIDTH: usize> = Poseidon2<
+/// It acts on arrays of the form `[Bn254Fr; WIDTH]`. See
+/// [`BN254_SUPPORTED_WIDTHS`] for the widths with an internal diffusion
+/// matrix implemented. `D` selects the S-box: any positive degree with
+/// `gcd(p - 1, D) = 1` is admissible (the default, [`BN254_S_BOX_DEGREE`],
+/// is 5), or [`BN254_S_BOX_DEGREE_INVERSE`] for the inverse S-box.
+pub type Poseidon2Bn254<const WIDTH: usize, const D: u64 = BN254_S_BOX_DEGREE> = Poseidon2<
     Bn254,
     Poseidon2ExternalLayerBn254<WIDTH>,
     Poseidon2InternalLayerBn254,
     WIDTH,
-    BN254_S_BOX_DEGREE,
+    D,
 >;
 
-/// Currently we only support a single width for Poseidon2 BN254.
-const BN254_WIDTH: usize = 3;
+/// A BN254 Poseidon2 instance using the inverse S-box `x -> x^{-1}` instead of
+/// a power map. Useful at widths or over fields where no small power `D`
+/// satisfies `gcd(p - 1, D) = 1`.
+pub type Poseidon2Bn254Inverse<const WIDTH: usize> =
+    Poseidon2Bn254<WIDTH, BN254_S_BOX_DEGREE_INVERSE>;
+
+/// Poseidon2 BN254 is supported for these widths, covering both a rate-1
+/// compression mode (`t = 2`) and a range of sponge rates for different
+/// Merkle-tree arities (`t = 3, 4, 8`).
+pub const BN254_SUPPORTED_WIDTHS: [usize; 4] = [2, 3, 4, 8];
 
 #[derive(Debug, Clone, Default)]
 pub struct Poseidon2InternalLayerBn254 {
@@ -45,6 +73,35 @@ impl InternalLayerConstructor<Bn254> for Poseidon2InternalLayerBn254 {
     }
 }
 
+/// Multiply `x` by the small positive integer `c` using only doubling and
+/// addition, avoiding a full field multiplication for the tiny diagonal
+/// entries used below.
+fn mul_small(x: Bn254, c: u64) -> Bn254 {
+    match c {
+        1 => x,
+        2 => x.double(),
+        3 => x.double() + x,
+        4 => x.double().double(),
+        5 => x.double().double() + x,
+        6 => (x.double() + x).double(),
+        7 => x.double().double().double() - x,
+        _ => unreachable!("no BN254 internal matrix uses a diagonal entry of {c}"),
+    }
+}
+
+/// A faster version of `matmul_internal` making use of the fact that
+/// the internal matrix is equal to:
+/// ```ignore
+///     1 + Diag([1, 2]) =   [2, 1]
+///                          [1, 3]
+/// ```
+fn bn254_matmul_internal_2(state: &mut [Bn254; 2]) {
+    let sum = state[0] + state[1];
+
+    state[0] += sum;
+    state[1] = state[1].double() + sum;
+}
+
 /// A faster version of `matmul_internal` making use of the fact that
 /// the internal matrix is equal to:
 /// ```ignore
@@ -52,7 +109,7 @@ impl InternalLayerConstructor<Bn254> for Poseidon2InternalLayerBn254 {
 ///     1 + Diag([1, 1, 2]) =   [1, 2, 1]
 ///                             [1, 1, 3]
 /// ```
-fn bn254_matmul_internal(state: &mut [Bn254; 3]) {
+fn bn254_matmul_internal_3(state: &mut [Bn254; 3]) {
     // We bracket in this way as the s-box is applied to state[0] so this lets us
     // begin this computation before the s-box finishes.
     let sum = state[0] + (state[1] + state[2]);
@@ -62,10 +119,140 @@ fn bn254_matmul_internal(state: &mut [Bn254; 3]) {
     state[2] = state[2].double() + sum;
 }
 
-impl InternalLayer<Bn254, BN254_WIDTH, BN254_S_BOX_DEGREE> for Poseidon2InternalLayerBn254 {
+/// A faster version of `matmul_internal` making use of the fact that
+/// the internal matrix is `1 + Diag([1, 1, 2, 3])`.
+fn bn254_matmul_internal_4(state: &mut [Bn254; 4]) {
+    let sum = state[0] + (state[1] + (state[2] + state[3]));
+
+    state[0] += sum;
+    state[1] += sum;
+    state[2] = state[2].double() + sum;
+    state[3] = mul_small(state[3], 3) + sum;
+}
+
+/// A faster version of `matmul_internal` making use of the fact that
+/// the internal matrix is `1 + Diag([1, 1, 2, 3, 4, 5, 6, 7])`.
+fn bn254_matmul_internal_8(state: &mut [Bn254; 8]) {
+    let sum = state
+        .iter()
+        .copied()
+        .fold(Bn254::ZERO, |acc, value| acc + value);
+
+    state[0] += sum;
+    state[1] += sum;
+    state[2] = state[2].double() + sum;
+    for i in 3..8 {
+        state[i] = mul_small(state[i], i as u64) + sum;
+    }
+}
+
+/// Add a round constant to `value` and apply the chosen S-box in place.
+///
+/// For `D == BN254_S_BOX_DEGREE_INVERSE` this is the inverse map `x -> x^{-1}`
+/// (sending `0` to itself); for every other `D` it is the power map `x ->
+/// x^D`, delegating to the generic power S-box shared with every other
+/// Poseidon2 instance. `D` is a compile-time constant, so this branch is
+/// resolved at monomorphization time with no runtime cost.
+fn bn254_add_rc_and_sbox<const D: u64>(value: &mut Bn254, rc: Bn254) {
+    // A compile-time assertion, not a `debug_assert!`: an inadmissible `D`
+    // makes the power map `x -> x^D` collide instead of permute, so this
+    // must reject bad instantiations in every profile, not just `cfg(debug_assertions)`.
+    // It lives in an inline `const` block rather than the internal/external
+    // layer constructors so it is checked exactly once per monomorphization
+    // of `D`, not repeated on every round of every permutation call.
+    const {
+        assert!(
+            bn254_sbox_degree_is_admissible(D),
+            "BN254 Poseidon2 S-box degree D is not admissible: gcd(p - 1, D) must equal 1"
+        )
+    };
+
+    if D == BN254_S_BOX_DEGREE_INVERSE {
+        *value += rc;
+        *value = value.try_inverse().unwrap_or(Bn254::ZERO);
+    } else {
+        add_rc_and_sbox_generic::<Bn254, D>(value, rc);
+    }
+}
+
+/// Whether the power S-box `x -> x^D` is a bijection on the BN254 scalar
+/// field, i.e. `gcd(p - 1, D) = 1` (the inverse S-box sentinel
+/// [`BN254_S_BOX_DEGREE_INVERSE`] is always admissible, since it isn't a
+/// power map). A `const fn`, computed entirely from `u64` arithmetic over
+/// the decimal modulus literal (rather than parsing a `BigUint`), so
+/// [`bn254_add_rc_and_sbox`] can assert it at compile time.
+const fn bn254_sbox_degree_is_admissible(d: u64) -> bool {
+    if d == BN254_S_BOX_DEGREE_INVERSE {
+        return true;
+    }
+
+    let p_mod_d = bn254_modulus_mod(d);
+    let p_minus_one_mod_d = (p_mod_d + d - 1) % d;
+
+    gcd_u64(d, p_minus_one_mod_d) == 1
+}
+
+/// `BN254_MODULUS mod d`, computed digit-by-digit via Horner's rule directly
+/// over the decimal literal, so it can run in a `const` context.
+const fn bn254_modulus_mod(d: u64) -> u64 {
+    let bytes = BN254_MODULUS.as_bytes();
+    let mut acc: u64 = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let digit = (bytes[i] - b'0') as u64;
+        acc = (acc * 10 + digit) % d;
+        i += 1;
+    }
+    acc
+}
+
+/// Greatest common divisor of two `u64`s via the Euclidean algorithm.
+const fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let next_b = a % b;
+        a = b;
+        b = next_b;
+    }
+    a
+}
+
+impl<const D: u64> InternalLayer<Bn254, 2, D> for Poseidon2InternalLayerBn254 {
+    /// Perform the internal layers of the Poseidon2 permutation on the given state.
+    fn permute_state(&self, state: &mut [Bn254; 2]) {
+        for &rc in &self.internal_constants {
+            bn254_add_rc_and_sbox::<D>(&mut state[0], rc);
+            bn254_matmul_internal_2(state);
+        }
+    }
+}
+
+impl<const D: u64> InternalLayer<Bn254, 3, D> for Poseidon2InternalLayerBn254 {
     /// Perform the internal layers of the Poseidon2 permutation on the given state.
-    fn permute_state(&self, state: &mut [Bn254; BN254_WIDTH]) {
-        internal_permute_state(state, bn254_matmul_internal, &self.internal_constants)
+    fn permute_state(&self, state: &mut [Bn254; 3]) {
+        for &rc in &self.internal_constants {
+            bn254_add_rc_and_sbox::<D>(&mut state[0], rc);
+            bn254_matmul_internal_3(state);
+        }
+    }
+}
+
+impl<const D: u64> InternalLayer<Bn254, 4, D> for Poseidon2InternalLayerBn254 {
+    /// Perform the internal layers of the Poseidon2 permutation on the given state.
+    fn permute_state(&self, state: &mut [Bn254; 4]) {
+        for &rc in &self.internal_constants {
+            bn254_add_rc_and_sbox::<D>(&mut state[0], rc);
+            bn254_matmul_internal_4(state);
+        }
+    }
+}
+
+impl<const D: u64> InternalLayer<Bn254, 8, D> for Poseidon2InternalLayerBn254 {
+    /// Perform the internal layers of the Poseidon2 permutation on the given state.
+    fn permute_state(&self, state: &mut [Bn254; 8]) {
+        for &rc in &self.internal_constants {
+            bn254_add_rc_and_sbox::<D>(&mut state[0], rc);
+            bn254_matmul_internal_8(state);
+        }
     }
 }
 
@@ -79,7 +266,7 @@ impl<const WIDTH: usize> ExternalLayerConstructor<Bn254, WIDTH>
     }
 }
 
-impl<const WIDTH: usize> ExternalLayer<Bn254, WIDTH, BN254_S_BOX_DEGREE>
+impl<const WIDTH: usize, const D: u64> ExternalLayer<Bn254, WIDTH, D>
     for Poseidon2ExternalLayerBn254<WIDTH>
 {
     /// Perform the initial external layers of the Poseidon2 permutation on the given state.
@@ -87,7 +274,7 @@ impl<const WIDTH: usize> ExternalLayer<Bn254, WIDTH, BN254_S_BOX_DEGREE>
         external_initial_permute_state(
             state,
             self.get_initial_constants(),
-            add_rc_and_sbox_generic,
+            bn254_add_rc_and_sbox::<D>,
             &HLMDSMat4,
         );
     }
@@ -97,12 +284,154 @@ impl<const WIDTH: usize> ExternalLayer<Bn254, WIDTH, BN254_S_BOX_DEGREE>
         external_terminal_permute_state(
             state,
             self.get_terminal_constants(),
-            add_rc_and_sbox_generic,
+            bn254_add_rc_and_sbox::<D>,
             &HLMDSMat4,
         );
     }
 }
 
+impl<const WIDTH: usize, const D: u64> Poseidon2Bn254<WIDTH, D> {
+    /// Construct a BN254 Poseidon2 permutation with `rounds_f` full rounds and
+    /// `rounds_p` partial rounds, deriving spec-compatible round constants
+    /// on the fly via the standard Poseidon Grain LFSR generator instead of
+    /// requiring the caller to vendor constants from a reference
+    /// implementation.
+    ///
+    /// The S-box id `D` is folded into the LFSR seed, so instances with a
+    /// different S-box (e.g. [`Poseidon2Bn254Inverse`]) get an independent
+    /// constant stream rather than reusing the default instance's constants.
+    /// The seed holds only the low 4 bits of `D`: degrees that agree modulo
+    /// 16, such as 7 and 23, get the same constants.
+    pub fn new_from_grain(rounds_f: usize, rounds_p: usize) -> Self {
+        let mut lfsr = GrainLfsr::new(D, WIDTH, rounds_f, rounds_p);
+
+        let mut round_constants: Vec<[Bn254; WIDTH]> = (0..rounds_f + rounds_p)
+            .map(|_| core::array::from_fn(|_| lfsr.next_field_element()))
+            .collect();
+
+        let internal_start = rounds_f / 2;
+        let internal_end = internal_start + rounds_p;
+        let internal_constants = round_constants
+            .drain(internal_start..internal_end)
+            .map(|vec| vec[0])
+            .collect::<Vec<_>>();
+        let external_constants = ExternalLayerConstants::new(
+            round_constants[..rounds_f / 2].to_vec(),
+            round_constants[rounds_f / 2..].to_vec(),
+        );
+
+        Self::new(external_constants, internal_constants)
+    }
+}
+
+/// Grain LFSR based round-constant generator, following the algorithm used
+/// to generate the constants for the reference Poseidon/Poseidon2
+/// implementations.
+///
+/// An 80-bit LFSR is seeded with the instance parameters (field type, S-box
+/// degree, modulus bit length, state width, and round counts), then run for
+/// 160 steps to mix the seed before any output is drawn. Output bits are
+/// produced using the self-shrinking generator rule: two raw LFSR bits are
+/// generated at a time, and the second is emitted only when the first is 1.
+struct GrainLfsr {
+    state: [bool; 80],
+    modulus: BigUint,
+}
+
+impl GrainLfsr {
+    /// Field-type tag for a prime field, per the Grain LFSR parameter
+    /// encoding.
+    const FIELD_PRIME: u64 = 1;
+
+    fn new(s_box_degree: u64, width: usize, rounds_f: usize, rounds_p: usize) -> Self {
+        let mut state = [false; 80];
+        let mut idx = 0;
+        let mut push_bits = |value: u64, n_bits: usize| {
+            for i in (0..n_bits).rev() {
+                state[idx] = (value >> i) & 1 == 1;
+                idx += 1;
+            }
+        };
+        push_bits(Self::FIELD_PRIME, 2);
+        push_bits(s_box_degree, 4);
+        push_bits(BN254_NUM_BITS as u64, 12);
+        push_bits(width as u64, 12);
+        push_bits(rounds_f as u64, 10);
+        push_bits(rounds_p as u64, 10);
+        for _ in 0..30 {
+            state[idx] = true;
+            idx += 1;
+        }
+        debug_assert_eq!(idx, 80);
+
+        let modulus = BN254_MODULUS.parse().expect("valid BN254 modulus literal");
+        let mut lfsr = Self { state, modulus };
+        for _ in 0..160 {
+            lfsr.update();
+        }
+        lfsr
+    }
+
+    /// Advance the LFSR by one step, returning the bit that was shifted in.
+    fn update(&mut self) -> bool {
+        let new_bit = self.state[0]
+            ^ self.state[13]
+            ^ self.state[23]
+            ^ self.state[38]
+            ^ self.state[51]
+            ^ self.state[62];
+        self.state.copy_within(1.., 0);
+        self.state[79] = new_bit;
+        new_bit
+    }
+
+    /// Draw a single output bit using the self-shrinking generator rule:
+    /// generate a pair of raw bits and emit the second only if the first is 1.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let b1 = self.update();
+            let b2 = self.update();
+            if b1 {
+                return b2;
+            }
+        }
+    }
+
+    /// Draw `BN254_NUM_BITS` bits MSB-first and accept the result as a field
+    /// element only if it lies below the modulus, redrawing a fresh batch of
+    /// bits otherwise (rejection sampling).
+    fn next_uint(&mut self) -> BigUint {
+        loop {
+            let mut value = BigUint::from(0u8);
+            for _ in 0..BN254_NUM_BITS {
+                value *= 2u8;
+                if self.next_bit() {
+                    value += 1u8;
+                }
+            }
+            if value < self.modulus {
+                return value;
+            }
+        }
+    }
+
+    /// Draw a field element and convert it into Monty form for use as a
+    /// Poseidon2 round constant.
+    fn next_field_element(&mut self) -> Bn254 {
+        let value = self.next_uint();
+
+        let mut bytes = value.to_bytes_le();
+        bytes.resize(32, 0);
+        let field_elem =
+            Bn254::from_bytes_monty(&bytes).expect("Grain LFSR output exceeds the BN254 modulus");
+
+        // `from_bytes_monty` does not convert into Monty form, so we do that
+        // ourselves, as in the zkhash-equivalence test below.
+        let monty_form = monty_mul(BN254_MONTY_R_SQ, field_elem.value);
+        Bn254::new_monty(monty_form)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use num_bigint::BigUint;
@@ -198,4 +527,165 @@ mod tests {
 
         assert_eq!(output, expected);
     }
+
+    /// Check the Grain LFSR constant generator against the same `zkhash`
+    /// reference implementation used above, rather than just checking that
+    /// `new_from_grain` produces *some* well-behaved permutation. A subtle
+    /// bit-order or off-by-one bug in the LFSR's field/degree/width/round-count
+    /// encoding, update taps, self-shrinking rule, or rejection sampling would
+    /// still produce a deterministic, non-identity permutation, so only a
+    /// comparison against a known-good oracle can catch it.
+    #[test]
+    fn test_poseidon2_bn254_grain_matches_reference() {
+        const WIDTH: usize = 3;
+        const ROUNDS_F: usize = 8;
+        const ROUNDS_P: usize = 56;
+
+        type F = Bn254;
+
+        let mut rng = SmallRng::seed_from_u64(2);
+
+        // Poseidon2 reference implementation from zkhash repo, using its own
+        // vendored constants.
+        let poseidon2_ref = Poseidon2Ref::new(&POSEIDON2_BN256_PARAMS);
+
+        // Our Poseidon2 implementation, with round constants derived on the
+        // fly via the Grain LFSR instead of vendored from `RC3`.
+        let poseidon2 = Poseidon2Bn254::<WIDTH>::new_from_grain(ROUNDS_F, ROUNDS_P);
+
+        let input = rng.random::<[F; WIDTH]>();
+        let input_ark_ff = input.map(ark_ff_from_bn254);
+
+        let output_ref: [ark_FpBN256; WIDTH] =
+            poseidon2_ref.permutation(&input_ark_ff).try_into().unwrap();
+        let expected: [F; WIDTH] = output_ref.map(bn254_from_ark_ff);
+
+        let mut output = input;
+        poseidon2.permute_mut(&mut output);
+
+        assert_eq!(
+            output, expected,
+            "Grain LFSR derived constants must match the zkhash reference round constants"
+        );
+    }
+
+    /// Apply the internal diffusion matrix `1 + Diag(diag)` to `state` via a
+    /// direct, unoptimized matrix multiply: row `i` of `1 + Diag(diag)` is
+    /// all-ones except for an extra `diag[i]` on the diagonal, so
+    /// `output[i] = sum(state) + diag[i] * state[i]`.
+    ///
+    /// This is independent of (and much slower than) `bn254_matmul_internal_*`,
+    /// which instead factor the multiply through a single shared `sum` plus a
+    /// handful of doublings; comparing the two catches a wrong diagonal entry
+    /// that a determinism/non-identity check cannot.
+    fn naive_matmul_internal<const WIDTH: usize>(state: &[Bn254; WIDTH], diag: &[u64; WIDTH]) -> [Bn254; WIDTH] {
+        let sum = state.iter().copied().fold(Bn254::ZERO, |acc, x| acc + x);
+        core::array::from_fn(|i| state[i] * Bn254::from_u64(diag[i]) + sum)
+    }
+
+    /// Check `bn254_matmul_internal_2/3/4/8` against [`naive_matmul_internal`]
+    /// on many random states, using the diagonal entries documented on each
+    /// optimized routine. HorizenLabs' `zkhash` reference only ships round
+    /// constants (and hence full-permutation vectors) for width 3, so this is
+    /// the independent verification the other widths' internal matrices have:
+    /// unlike a determinism/non-identity check on the full permutation, a
+    /// wrong diagonal entry here cannot pass by accident.
+    #[test]
+    fn test_bn254_matmul_internal_matches_naive_diag() {
+        const TRIALS: usize = 100;
+
+        let mut rng = SmallRng::seed_from_u64(3);
+
+        for _ in 0..TRIALS {
+            let state: [Bn254; 2] = rng.random();
+            let expected = naive_matmul_internal(&state, &[1, 2]);
+            let mut actual = state;
+            bn254_matmul_internal_2(&mut actual);
+            assert_eq!(actual, expected, "width-2 internal matmul diagonal is wrong");
+        }
+
+        for _ in 0..TRIALS {
+            let state: [Bn254; 3] = rng.random();
+            let expected = naive_matmul_internal(&state, &[1, 1, 2]);
+            let mut actual = state;
+            bn254_matmul_internal_3(&mut actual);
+            assert_eq!(actual, expected, "width-3 internal matmul diagonal is wrong");
+        }
+
+        for _ in 0..TRIALS {
+            let state: [Bn254; 4] = rng.random();
+            let expected = naive_matmul_internal(&state, &[1, 1, 2, 3]);
+            let mut actual = state;
+            bn254_matmul_internal_4(&mut actual);
+            assert_eq!(actual, expected, "width-4 internal matmul diagonal is wrong");
+        }
+
+        for _ in 0..TRIALS {
+            let state: [Bn254; 8] = rng.random();
+            let expected = naive_matmul_internal(&state, &[1, 1, 2, 3, 4, 5, 6, 7]);
+            let mut actual = state;
+            bn254_matmul_internal_8(&mut actual);
+            assert_eq!(actual, expected, "width-8 internal matmul diagonal is wrong");
+        }
+    }
+
+    /// HorizenLabs' `zkhash` reference only ships round constants for width 3,
+    /// so the additional widths have no external reference vector for the
+    /// *full permutation* to compare against (the internal diffusion matrix
+    /// itself is independently checked by
+    /// `test_bn254_matmul_internal_matches_naive_diag` above). Round out that
+    /// check with the properties any correct permutation must have:
+    /// determinism, and that it actually mixes the state rather than acting
+    /// as (e.g.) the identity.
+    #[test]
+    fn test_poseidon2_bn254_additional_widths() {
+        const ROUNDS_F: usize = 8;
+        const ROUNDS_P: usize = 56;
+
+        fn check_permutation<const WIDTH: usize>() {
+            type F = Bn254;
+
+            let mut rng = SmallRng::seed_from_u64(1);
+            let poseidon2 = Poseidon2Bn254::<WIDTH>::new_from_grain(ROUNDS_F, ROUNDS_P);
+
+            let input = rng.random::<[F; WIDTH]>();
+
+            let mut output = input;
+            poseidon2.permute_mut(&mut output);
+            assert_ne!(output, input, "Poseidon2 must not be the identity permutation");
+
+            let mut output_again = input;
+            poseidon2.permute_mut(&mut output_again);
+            assert_eq!(output, output_again, "Poseidon2 must be deterministic");
+        }
+
+        check_permutation::<2>();
+        check_permutation::<4>();
+        check_permutation::<8>();
+    }
+
+    /// Sanity-check the alternate inverse-S-box instance the same way as
+    /// `test_poseidon2_bn254_additional_widths`, since it likewise has no
+    /// external reference vector to compare against.
+    #[test]
+    fn test_poseidon2_bn254_inverse_sbox() {
+        const WIDTH: usize = 3;
+        const ROUNDS_F: usize = 8;
+        const ROUNDS_P: usize = 56;
+
+        type F = Bn254;
+
+        let mut rng = SmallRng::seed_from_u64(1);
+        let poseidon2 = Poseidon2Bn254Inverse::<WIDTH>::new_from_grain(ROUNDS_F, ROUNDS_P);
+
+        let input = rng.random::<[F; WIDTH]>();
+
+        let mut output = input;
+        poseidon2.permute_mut(&mut output);
+        assert_ne!(output, input, "Poseidon2 must not be the identity permutation");
+
+        let mut output_again = input;
+        poseidon2.permute_mut(&mut output_again);
+        assert_eq!(output, output_again, "Poseidon2 must be deterministic");
+    }
 }
@@ -1,7 +1,204 @@
-//! Eventually this will hold a vectorized Neon implementation of Poseidon2 for PackedKoalaBearNeon
-//! Currently this is essentially a placeholder to allow compilation and testing on Neon devices.
+//! Vectorized Neon implementation of Poseidon2 for `PackedKoalaBearNeon`.
 //!
-//! Converting the AVX2/AVX512 code across to Neon is on the TODO list.
+//! This mirrors the existing x86 AVX2/AVX512 packed implementations: round
+//! constants are added and the S-box applied directly to `int32x4_t`/`uint32x4_t`
+//! lanes via a packed Montgomery cube, the external layer reuses the
+//! HL-MDS-Mat4 mixing matrix and the internal layer applies the `1 + Diag(...)`
+//! diffusion matrix lane-wise, all without ever dropping down to a per-lane
+//! scalar permute.
+
+use core::arch::aarch64::{self, uint32x4_t};
+use core::mem::transmute;
+
+use p3_poseidon2::{
+    ExternalLayer, ExternalLayerConstants, ExternalLayerConstructor, HLMDSMat4, InternalLayer,
+    InternalLayerConstructor, external_initial_permute_state, external_terminal_permute_state,
+};
+
+use crate::{
+    KoalaBear, KOALABEAR_INTERNAL_DIAG_SHIFTS_16, KOALABEAR_INTERNAL_DIAG_SHIFTS_24,
+    PackedKoalaBearNeon, Poseidon2ExternalLayerKoalaBear, Poseidon2InternalLayerKoalaBear,
+};
+
+/// The KoalaBear prime `P = 2^31 - 2^24 + 1`.
+const P: u32 = 0x7f000001;
+
+/// `MU = -P^{-1} mod 2^32`, the Montgomery reduction constant for `P`.
+const MONTY_MU: u32 = 0x7effffff;
+
+/// Montgomery reduce a pair of 64-bit products held in `lo`/`hi`, returning the
+/// reduced values packed back into the lanes of a single `uint32x4_t`.
+///
+/// `lo` and `hi` each hold two 64-bit products in their even/odd 32-bit lanes,
+/// since Neon only has widening multiplies that double a 2-lane `uint32x2_t`
+/// into a `uint64x2_t` at a time.
+#[inline(always)]
+unsafe fn monty_reduce(lo: aarch64::uint64x2_t, hi: aarch64::uint64x2_t) -> uint32x4_t {
+    // `vmovn_u64` narrows a 2-lane `uint64x2_t` down to a 2-lane `uint32x2_t`,
+    // so the multiply by `MONTY_MU` and the following widening multiply by
+    // `P` both operate on 2-lane (`_u32`, not `_q_u32`) vectors.
+    let mu = aarch64::vdup_n_u32(MONTY_MU);
+    let p2 = aarch64::vdup_n_u32(P);
+    let p4 = aarch64::vdupq_n_u32(P);
+
+    let t_lo = aarch64::vmul_u32(aarch64::vmovn_u64(lo), mu);
+    let t_hi = aarch64::vmul_u32(aarch64::vmovn_u64(hi), mu);
+
+    let tp_lo = aarch64::vmull_u32(t_lo, p2);
+    let tp_hi = aarch64::vmull_u32(t_hi, p2);
+
+    let sum_lo = aarch64::vaddq_u64(lo, tp_lo);
+    let sum_hi = aarch64::vaddq_u64(hi, tp_hi);
+
+    // The low 32 bits of `lo + t * P` are guaranteed to be zero by construction
+    // of `t`, so the reduced value sits in the high halves of each 64-bit lane.
+    let reduced = aarch64::vcombine_u32(
+        aarch64::vshrn_n_u64::<32>(sum_lo),
+        aarch64::vshrn_n_u64::<32>(sum_hi),
+    );
+
+    // The result of the reduction lies in `[0, 2P)`; bring it back into range.
+    aarch64::vminq_u32(reduced, aarch64::vsubq_u32(reduced, p4))
+}
+
+/// Packed Montgomery multiplication of two vectors of four Monty-form
+/// `KoalaBear` elements.
+#[inline(always)]
+unsafe fn packed_monty_mul(lhs: uint32x4_t, rhs: uint32x4_t) -> uint32x4_t {
+    let lo = aarch64::vmull_u32(aarch64::vget_low_u32(lhs), aarch64::vget_low_u32(rhs));
+    let hi = aarch64::vmull_high_u32(lhs, rhs);
+    monty_reduce(lo, hi)
+}
+
+/// Add a vector of round constants (already in Monty form) to `state`,
+/// reducing back into `[0, P)`.
+#[inline(always)]
+unsafe fn add_rc(state: uint32x4_t, rc: uint32x4_t) -> uint32x4_t {
+    let p = aarch64::vdupq_n_u32(P);
+    let sum = aarch64::vaddq_u32(state, rc);
+    aarch64::vminq_u32(sum, aarch64::vsubq_u32(sum, p))
+}
+
+/// Apply the degree-3 S-box `x -> x^3` lane-wise via two packed Montgomery
+/// multiplies.
+#[inline(always)]
+unsafe fn packed_cube(x: uint32x4_t) -> uint32x4_t {
+    let x2 = packed_monty_mul(x, x);
+    packed_monty_mul(x2, x)
+}
+
+/// Add a round constant and apply the S-box to a single packed
+/// lane-vector, operating directly on Neon vectors. The external layer
+/// combinators (`external_initial_permute_state`/
+/// `external_terminal_permute_state`) call this once per state element,
+/// driving the per-`WIDTH` iteration themselves.
+#[inline(always)]
+fn add_rc_and_sbox_packed(value: &mut PackedKoalaBearNeon, rc: KoalaBear) {
+    unsafe {
+        let x: uint32x4_t = transmute(*value);
+        let rc_vec = aarch64::vdupq_n_u32(rc.value);
+        let summed = add_rc(x, rc_vec);
+        let cubed = packed_cube(summed);
+        *value = transmute::<uint32x4_t, PackedKoalaBearNeon>(cubed);
+    }
+}
+
+/// Apply the `1 + Diag(shifts)` internal diffusion matrix lane-wise: every
+/// limb is multiplied by `2^shift` (every diagonal entry of the KoalaBear
+/// internal matrix is a power of two) and the shared row sum is added back in.
+///
+/// `shift` varies per `WIDTH` index but is the same across all four packed
+/// lanes of a given limb, so we splat the Monty form of `2^shift` into a
+/// vector and use a packed Montgomery multiply, which keeps the result
+/// reduced modulo `P` (a bare lane shift would wrap modulo `2^32` instead).
+fn internal_matmul_packed<const WIDTH: usize>(
+    state: &mut [PackedKoalaBearNeon; WIDTH],
+    shifts: &[u8; WIDTH],
+) {
+    let sum = state
+        .iter()
+        .copied()
+        .fold(PackedKoalaBearNeon::default(), |acc, x| acc + x);
+
+    for (value, &shift) in state.iter_mut().zip(shifts.iter()) {
+        unsafe {
+            let x: uint32x4_t = transmute(*value);
+            // Monty form of `2^shift` is `2^(shift + 32) mod P`.
+            let factor = ((1u128 << (shift as u32 + 32)) % P as u128) as u32;
+            let shifted = packed_monty_mul(x, aarch64::vdupq_n_u32(factor));
+            let scaled = transmute::<uint32x4_t, PackedKoalaBearNeon>(shifted);
+            *value = scaled + sum;
+        }
+    }
+}
+
+/// Add a single round constant to `state[0]` and apply the S-box to it in
+/// place, as required by the internal Poseidon2 layer.
+#[inline(always)]
+fn add_rc_and_sbox_packed_lane0<const WIDTH: usize>(
+    state: &mut [PackedKoalaBearNeon; WIDTH],
+    rc: KoalaBear,
+) {
+    add_rc_and_sbox_packed(&mut state[0], rc);
+}
+
+impl InternalLayer<PackedKoalaBearNeon, 16, 3> for Poseidon2InternalLayerKoalaBear {
+    fn permute_state(&self, state: &mut [PackedKoalaBearNeon; 16]) {
+        for &rc in &self.internal_constants {
+            add_rc_and_sbox_packed_lane0(state, rc);
+            internal_matmul_packed(state, &KOALABEAR_INTERNAL_DIAG_SHIFTS_16);
+        }
+    }
+}
+
+impl InternalLayer<PackedKoalaBearNeon, 24, 3> for Poseidon2InternalLayerKoalaBear {
+    fn permute_state(&self, state: &mut [PackedKoalaBearNeon; 24]) {
+        for &rc in &self.internal_constants {
+            add_rc_and_sbox_packed_lane0(state, rc);
+            internal_matmul_packed(state, &KOALABEAR_INTERNAL_DIAG_SHIFTS_24);
+        }
+    }
+}
+
+impl ExternalLayer<PackedKoalaBearNeon, 16, 3> for Poseidon2ExternalLayerKoalaBear<16> {
+    fn permute_state_initial(&self, state: &mut [PackedKoalaBearNeon; 16]) {
+        external_initial_permute_state(
+            state,
+            self.get_initial_constants(),
+            add_rc_and_sbox_packed,
+            &HLMDSMat4,
+        );
+    }
+
+    fn permute_state_terminal(&self, state: &mut [PackedKoalaBearNeon; 16]) {
+        external_terminal_permute_state(
+            state,
+            self.get_terminal_constants(),
+            add_rc_and_sbox_packed,
+            &HLMDSMat4,
+        );
+    }
+}
+
+impl ExternalLayer<PackedKoalaBearNeon, 24, 3> for Poseidon2ExternalLayerKoalaBear<24> {
+    fn permute_state_initial(&self, state: &mut [PackedKoalaBearNeon; 24]) {
+        external_initial_permute_state(
+            state,
+            self.get_initial_constants(),
+            add_rc_and_sbox_packed,
+            &HLMDSMat4,
+        );
+    }
+
+    fn permute_state_terminal(&self, state: &mut [PackedKoalaBearNeon; 24]) {
+        external_terminal_permute_state(
+            state,
+            self.get_terminal_constants(),
+            add_rc_and_sbox_packed,
+            &HLMDSMat4,
+        );
+    }
+}
 
 #[cfg(test)]
 mod tests {
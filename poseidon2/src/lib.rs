@@ -0,0 +1,6 @@
+//! Generic Poseidon2 constructions that build on top of a concrete
+//! `Permutation` implementation, such as the BN254 or KoalaBear instances.
+
+#![no_std]
+
+pub mod sponge;
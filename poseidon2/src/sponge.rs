@@ -0,0 +1,250 @@
+//! A duplex-sponge construction over any fixed-width Poseidon2 permutation,
+//! letting instances such as BN254 width-3 or KoalaBear hash arbitrary-length
+//! sequences of field elements rather than only fixed-width state.
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_field::PrimeCharacteristicRing;
+use p3_symmetric::Permutation;
+
+/// Which half of the duplex cycle the sponge is currently in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Accumulating input elements into the rate portion of the state.
+    Absorbing,
+    /// Reading output elements back out of the rate portion of the state.
+    Squeezing,
+}
+
+/// A duplex sponge built on top of a `WIDTH`-wide Poseidon2 permutation,
+/// following the Orchard sponge construction: the state is split into a
+/// `RATE`-element rate portion, through which absorbed/squeezed elements
+/// pass, and a `CAPACITY`-element capacity portion, which is never directly
+/// exposed and carries the sponge's security margin.
+///
+/// `WIDTH` must equal `RATE + CAPACITY`; [`Self::new`] checks this with a
+/// `debug_assert_eq!`, so only in builds with debug assertions enabled.
+#[derive(Clone, Debug)]
+pub struct Poseidon2Sponge<F, Perm, const WIDTH: usize, const RATE: usize, const CAPACITY: usize> {
+    permutation: Perm,
+    state: [F; WIDTH],
+    mode: Mode,
+    /// Index of the next rate element to read from or write into `state`.
+    pos: usize,
+}
+
+impl<F, Perm, const WIDTH: usize, const RATE: usize, const CAPACITY: usize>
+    Poseidon2Sponge<F, Perm, WIDTH, RATE, CAPACITY>
+where
+    F: PrimeCharacteristicRing + Copy,
+    Perm: Permutation<[F; WIDTH]>,
+{
+    /// Create a new sponge, wrapping `permutation`, with an all-zero initial
+    /// state.
+    pub fn new(permutation: Perm) -> Self {
+        debug_assert_eq!(WIDTH, RATE + CAPACITY, "WIDTH must equal RATE + CAPACITY");
+        Self {
+            permutation,
+            state: [F::ZERO; WIDTH],
+            mode: Mode::Absorbing,
+            pos: 0,
+        }
+    }
+
+    /// Run the underlying permutation over the full state and reset the
+    /// rate cursor.
+    fn permute(&mut self) {
+        self.permutation.permute_mut(&mut self.state);
+        self.pos = 0;
+    }
+
+    /// Absorb a sequence of field elements into the sponge, permuting
+    /// whenever the rate portion of the state fills up.
+    pub fn absorb(&mut self, input: &[F]) {
+        if self.mode == Mode::Squeezing {
+            // Absorbing after having squeezed starts a fresh block.
+            self.mode = Mode::Absorbing;
+            self.pos = 0;
+        }
+
+        for &value in input {
+            if self.pos == RATE {
+                self.permute();
+            }
+            self.state[self.pos] += value;
+            self.pos += 1;
+        }
+    }
+
+    /// Squeeze `n` field elements out of the sponge, permuting whenever the
+    /// rate portion of the state is exhausted.
+    pub fn squeeze(&mut self, n: usize) -> Vec<F> {
+        if self.mode == Mode::Absorbing {
+            // Finish the absorb phase with a final permutation, even if the
+            // last block was partial or empty, before reading output.
+            self.permute();
+            self.mode = Mode::Squeezing;
+        }
+
+        (0..n)
+            .map(|_| {
+                if self.pos == RATE {
+                    self.permute();
+                }
+                let value = self.state[self.pos];
+                self.pos += 1;
+                value
+            })
+            .collect()
+    }
+
+    /// Absorb `input` and squeeze `OUT` elements in a single call.
+    pub fn hash<const OUT: usize>(permutation: Perm, input: &[F]) -> [F; OUT] {
+        let mut sponge = Self::new(permutation);
+        sponge.absorb(input);
+        let output = sponge.squeeze(OUT);
+        output
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("squeeze(OUT) always returns OUT elements"))
+    }
+}
+
+/// A domain-separation tag for sponges over an input of fixed, known length
+/// `L`. The capacity element is initialized with a tag encoding `L`, and the
+/// input is padded with zeros up to a multiple of `RATE`, matching the
+/// standard security argument for fixed-input-length sponge hashing.
+#[derive(Clone, Copy, Debug)]
+pub struct ConstantLength<const L: usize>;
+
+impl<const L: usize> ConstantLength<L> {
+    /// Hash exactly `L` field elements, returning `OUT` output elements.
+    pub fn hash<F, Perm, const WIDTH: usize, const RATE: usize, const CAPACITY: usize, const OUT: usize>(
+        permutation: Perm,
+        input: [F; L],
+    ) -> [F; OUT]
+    where
+        F: PrimeCharacteristicRing + Copy,
+        Perm: Permutation<[F; WIDTH]>,
+    {
+        let mut sponge = Poseidon2Sponge::<F, Perm, WIDTH, RATE, CAPACITY>::new(permutation);
+
+        // Domain-separate by the declared input length, so that calls with
+        // different `L` can never collide on the same initial state.
+        sponge.state[RATE] = F::from_u64(L as u64);
+
+        sponge.absorb(&input);
+        let padding = (RATE - (L % RATE)) % RATE;
+        sponge.absorb(&vec![F::ZERO; padding]);
+
+        let output = sponge.squeeze(OUT);
+        output
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("squeeze(OUT) always returns OUT elements"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_bn254::{Bn254, Poseidon2Bn254};
+    use p3_field::PrimeCharacteristicRing;
+    use p3_koala_bear::{KoalaBear, Poseidon2KoalaBear};
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    /// Absorbing the same input into two fresh sponges over the BN254
+    /// permutation must squeeze out the same output, and the output must
+    /// actually depend on the input.
+    #[test]
+    fn test_sponge_round_trip_bn254() {
+        const WIDTH: usize = 3;
+        const RATE: usize = 2;
+        const CAPACITY: usize = 1;
+
+        let input = [Bn254::ONE, Bn254::TWO, Bn254::ONE + Bn254::TWO, Bn254::ZERO];
+
+        let permutation = Poseidon2Bn254::<WIDTH>::new_from_grain(8, 56);
+        let mut sponge = Poseidon2Sponge::<Bn254, _, WIDTH, RATE, CAPACITY>::new(permutation);
+        sponge.absorb(&input);
+        let output = sponge.squeeze(4);
+
+        let permutation = Poseidon2Bn254::<WIDTH>::new_from_grain(8, 56);
+        let mut sponge_again = Poseidon2Sponge::<Bn254, _, WIDTH, RATE, CAPACITY>::new(permutation);
+        sponge_again.absorb(&input);
+        let output_again = sponge_again.squeeze(4);
+
+        assert_eq!(
+            output, output_again,
+            "absorbing the same input twice must squeeze the same output"
+        );
+
+        let permutation = Poseidon2Bn254::<WIDTH>::new_from_grain(8, 56);
+        let mut empty_sponge = Poseidon2Sponge::<Bn254, _, WIDTH, RATE, CAPACITY>::new(permutation);
+        empty_sponge.absorb(&[]);
+        let empty_output = empty_sponge.squeeze(4);
+
+        assert_ne!(output, empty_output, "output must actually depend on the absorbed input");
+    }
+
+    /// Same round-trip property, but over the KoalaBear Neon-friendly width-16
+    /// permutation, to exercise the sponge against a second field and a
+    /// different `WIDTH`/`RATE`/`CAPACITY` split.
+    #[test]
+    fn test_sponge_round_trip_koala_bear() {
+        const WIDTH: usize = 16;
+        const RATE: usize = 8;
+        const CAPACITY: usize = 8;
+
+        let mut rng = SmallRng::seed_from_u64(1);
+        let permutation = Poseidon2KoalaBear::<WIDTH>::new_from_rng_128(&mut rng);
+        let input: [KoalaBear; 20] = rng.random();
+
+        let mut sponge = Poseidon2Sponge::<KoalaBear, _, WIDTH, RATE, CAPACITY>::new(permutation);
+        sponge.absorb(&input);
+        let output = sponge.squeeze(RATE);
+
+        let mut rng_again = SmallRng::seed_from_u64(1);
+        let permutation_again = Poseidon2KoalaBear::<WIDTH>::new_from_rng_128(&mut rng_again);
+        let input_again: [KoalaBear; 20] = rng_again.random();
+        let mut sponge_again =
+            Poseidon2Sponge::<KoalaBear, _, WIDTH, RATE, CAPACITY>::new(permutation_again);
+        sponge_again.absorb(&input_again);
+        let output_again = sponge_again.squeeze(RATE);
+
+        assert_eq!(
+            output, output_again,
+            "absorbing the same input twice must squeeze the same output"
+        );
+    }
+
+    /// `ConstantLength` must domain-separate on the declared length `L`, so
+    /// that hashing the same elements under a different claimed length never
+    /// collides, even once the shorter input is zero-padded out to `L`.
+    #[test]
+    fn test_constant_length_differentiates_on_length() {
+        const WIDTH: usize = 3;
+        const RATE: usize = 2;
+        const CAPACITY: usize = 1;
+
+        let permutation = Poseidon2Bn254::<WIDTH>::new_from_grain(8, 56);
+        let out_2: [Bn254; 1] = ConstantLength::<2>::hash::<_, _, WIDTH, RATE, CAPACITY, 1>(
+            permutation,
+            [Bn254::ONE, Bn254::TWO],
+        );
+
+        let permutation = Poseidon2Bn254::<WIDTH>::new_from_grain(8, 56);
+        let out_3: [Bn254; 1] = ConstantLength::<3>::hash::<_, _, WIDTH, RATE, CAPACITY, 1>(
+            permutation,
+            [Bn254::ONE, Bn254::TWO, Bn254::ZERO],
+        );
+
+        assert_ne!(
+            out_2, out_3,
+            "different declared lengths must domain-separate even over the same padded input"
+        );
+    }
+}